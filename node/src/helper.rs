@@ -0,0 +1,186 @@
+use crate::core::RoundNumber;
+use crate::crypto::{Digest, PublicKey};
+use crate::messages::Block;
+use crate::network::NetMessage;
+use crate::store::Store;
+use crate::timer::TimerManager;
+use futures::future::FutureExt as _;
+use futures::select;
+use log::{debug, error, warn};
+use std::collections::HashMap;
+use tokio::sync::mpsc::{channel, Receiver, Sender};
+
+#[cfg(test)]
+#[path = "tests/helper_tests.rs"]
+pub mod helper_tests;
+
+/// Maximum number of store reads a single peer may charge against within one
+/// budget window before the `Helper` starts dropping its requests. A `Single`
+/// request costs one read; a `Range` request costs one read per block it
+/// actually walks, so a peer can't buy several disk reads for the price of one
+/// request by asking for ranges instead of single blocks.
+const MAX_REQUESTS_PER_PEER: usize = 50;
+
+/// An inbound request for the `Helper` to serve, as dispatched from the network layer.
+pub enum HelperRequest {
+    /// Ask for a single block by digest.
+    Single(Digest, PublicKey),
+    /// Ask for a contiguous run of ancestor blocks, starting at `from_digest` and
+    /// walking backwards through `previous()` pointers down to `up_to_round`.
+    Range(Digest, RoundNumber, PublicKey),
+}
+
+/// A task, spawned alongside the `Synchronizer`, that serves inbound sync requests
+/// by looking up the requested block(s) in the `Store` and replying to the requester.
+pub struct Helper {
+    store: Store,
+    network_channel: Sender<NetMessage>,
+    rx_requests: Receiver<HelperRequest>,
+    budgets: HashMap<PublicKey, usize>,
+    max_range_len: RoundNumber,
+}
+
+/// Returns `false` (and leaves a record of the attempt) once `requester` has
+/// exceeded `MAX_REQUESTS_PER_PEER` within `budgets`' current window.
+fn charge_budget(budgets: &mut HashMap<PublicKey, usize>, requester: PublicKey) -> bool {
+    let used = budgets.entry(requester).or_insert(0);
+    if *used >= MAX_REQUESTS_PER_PEER {
+        warn!(
+            "Ignoring sync request from {}: budget of {} requests exceeded",
+            requester, MAX_REQUESTS_PER_PEER
+        );
+        return false;
+    }
+    *used += 1;
+    true
+}
+
+impl Helper {
+    pub async fn spawn(
+        store: Store,
+        network_channel: Sender<NetMessage>,
+        rx_requests: Receiver<HelperRequest>,
+        mut timer_manager: TimerManager,
+        budget_reset_delay: u64,
+        max_range_len: RoundNumber,
+    ) {
+        let (tx_timer, mut rx_timer) = channel(100);
+        timer_manager
+            .schedule(budget_reset_delay, "helper".to_string(), tx_timer.clone())
+            .await;
+
+        tokio::spawn(async move {
+            let mut helper = Self {
+                store,
+                network_channel,
+                rx_requests,
+                budgets: HashMap::new(),
+                max_range_len,
+            };
+            loop {
+                select! {
+                    request = helper.rx_requests.recv().fuse() => {
+                        match request {
+                            Some(HelperRequest::Single(digest, requester)) => {
+                                helper.handle_request(digest, requester).await
+                            },
+                            Some(HelperRequest::Range(from_digest, up_to_round, requester)) => {
+                                helper.handle_range_request(from_digest, up_to_round, requester).await
+                            },
+                            None => return,
+                        }
+                    },
+                    notification = rx_timer.recv().fuse() => {
+                        if notification.is_some() {
+                            // Reset every peer's budget at the start of a new window.
+                            helper.budgets.clear();
+                            timer_manager
+                                .schedule(budget_reset_delay, "helper".to_string(), tx_timer.clone())
+                                .await;
+                        }
+                    }
+                }
+            }
+        });
+    }
+
+    /// Look up `digest` in the store and reply to `requester`, unless they have
+    /// exhausted their request budget for this window.
+    async fn handle_request(&mut self, digest: Digest, requester: PublicKey) {
+        if !charge_budget(&mut self.budgets, requester) {
+            return;
+        }
+
+        match self.store.read(digest.to_vec()).await {
+            Ok(Some(bytes)) => match bincode::deserialize(&bytes) {
+                Ok(block) => {
+                    let message = NetMessage::SyncReply(block, requester);
+                    if let Err(e) = self.network_channel.send(message).await {
+                        panic!("Failed to send Sync Reply to network: {}", e);
+                    }
+                }
+                Err(e) => error!("Failed to deserialize stored block: {}", e),
+            },
+            // The digest is not (yet) in our store: nothing to serve.
+            Ok(None) => debug!("Ignoring sync request for unknown digest {:?}", digest),
+            Err(e) => error!("{}", e),
+        }
+    }
+
+    /// Walk backwards from `from_digest` through `previous()` pointers, collecting
+    /// blocks down to `up_to_round` (or `max_range_len` blocks, whichever comes
+    /// first), and send them all back to `requester` in a single reply.
+    ///
+    /// Each store read attempted along the way charges its own token against
+    /// `requester`'s budget, the same as a `Single` request does: a range
+    /// request that walks N blocks costs N reads, not one, so a peer can't get
+    /// a cheaper rate on disk reads by batching them into ranges.
+    async fn handle_range_request(
+        &mut self,
+        from_digest: Digest,
+        up_to_round: RoundNumber,
+        requester: PublicKey,
+    ) {
+        let mut blocks = Vec::new();
+        let mut next = Some(from_digest.clone());
+        while let Some(digest) = next {
+            if blocks.len() >= self.max_range_len as usize {
+                break;
+            }
+            if !charge_budget(&mut self.budgets, requester) {
+                break;
+            }
+            next = match self.store.read(digest.to_vec()).await {
+                Ok(Some(bytes)) => match bincode::deserialize::<Block>(&bytes) {
+                    Ok(block) => {
+                        let reached_floor = block.round <= up_to_round;
+                        let previous = block.previous().clone();
+                        blocks.push(block);
+                        if reached_floor {
+                            None
+                        } else {
+                            Some(previous)
+                        }
+                    }
+                    Err(e) => {
+                        error!("Failed to deserialize stored block: {}", e);
+                        None
+                    }
+                },
+                // Nothing further back that we know of: serve what we collected so far.
+                Ok(None) => None,
+                Err(e) => {
+                    error!("{}", e);
+                    None
+                }
+            };
+        }
+
+        if !blocks.is_empty() {
+            let message = NetMessage::SyncRangeReply(from_digest, blocks, requester);
+            if let Err(e) = self.network_channel.send(message).await {
+                panic!("Failed to send Sync Range Reply to network: {}", e);
+            }
+        }
+    }
+}
@@ -1,7 +1,9 @@
 use crate::core::{CoreMessage, RoundNumber};
+use crate::crypto::Digest;
 use crate::crypto::Hash as _;
 use crate::crypto::PublicKey;
-use crate::error::ConsensusResult;
+use crate::config::Committee;
+use crate::error::{ConsensusError, ConsensusResult};
 use crate::messages::{Block, QC};
 use crate::network::NetMessage;
 use crate::store::Store;
@@ -10,37 +12,189 @@ use futures::future::FutureExt as _;
 use futures::select;
 use futures::stream::futures_unordered::FuturesUnordered;
 use futures::stream::StreamExt as _;
-use log::{debug, error};
+use log::debug;
 use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast;
 use tokio::sync::mpsc::{channel, Receiver, Sender};
 
 #[cfg(test)]
 #[path = "tests/synchronizer_tests.rs"]
 pub mod synchronizer_tests;
 
+/// How many targeted attempts (author, then other peers round-robin) to make
+/// before giving up and broadcasting the request to the whole network.
+const MAX_TARGETED_ATTEMPTS: usize = 3;
+
+/// Retries back off exponentially from `sync_retry_delay`, capped at this many
+/// times the base delay.
+const RETRY_DELAY_CEILING_MULTIPLIER: u64 = 16;
+
+/// After this many total retry attempts (targeted and broadcast combined) with
+/// no delivery, stop retrying the digest altogether, free its pending-parent
+/// slot, and emit `SyncEvent::Failed` instead of retrying forever. Without
+/// this, a peer that proposes a block with an unresolvable parent can fill
+/// `max_pending_parents` with entries that never free up.
+const MAX_RETRY_ATTEMPTS: usize = 8;
+
 enum SyncMessage {
     SyncParent(Vec<u8>, Block),
     SyncPayload(Vec<u8>, Block, Receiver<()>),
 }
 
+/// Per-digest retry bookkeeping for the liveness-retry loop: how many times we
+/// have asked, who we have already asked, and when the next attempt is due.
+struct RetryState {
+    wait_on: Vec<u8>,
+    author: PublicKey,
+    attempt: usize,
+    next_due: Instant,
+    delay: u64,
+    queried: HashSet<PublicKey>,
+}
+
+impl RetryState {
+    fn new(wait_on: Vec<u8>, author: PublicKey, base_delay: u64) -> Self {
+        Self {
+            wait_on,
+            author,
+            attempt: 0,
+            next_due: Instant::now(),
+            delay: base_delay,
+            queried: HashSet::new(),
+        }
+    }
+
+    /// Pick the next peer to target: the block's author first, then the other
+    /// known authorities round-robin, skipping whoever we already queried this
+    /// pass (unless everyone has already been asked).
+    fn next_target(&mut self, others: &[PublicKey]) -> PublicKey {
+        let target = if self.queried.is_empty() {
+            self.author
+        } else {
+            others
+                .iter()
+                .find(|peer| !self.queried.contains(peer))
+                .copied()
+                .unwrap_or(self.author)
+        };
+        self.queried.insert(target);
+        target
+    }
+
+    fn backoff(&mut self, base_delay: u64) {
+        self.attempt += 1;
+        self.delay = (self.delay * 2).min(base_delay * RETRY_DELAY_CEILING_MULTIPLIER);
+        self.next_due = Instant::now() + Duration::from_millis(self.delay);
+    }
+}
+
+/// Runtime policy for how much the `Synchronizer` is allowed to buffer before it
+/// starts rejecting new work, instead of queuing it optimistically.
+#[derive(Clone, Copy, Debug)]
+pub struct SyncConfig {
+    /// Maximum number of blocks that can be waiting on a missing parent at once.
+    pub max_pending_parents: usize,
+    /// Maximum number of blocks that can be waiting on a missing payload at once.
+    pub max_pending_payloads: usize,
+    /// Capacity of the channel feeding the spawned synchronizer task.
+    pub inner_channel_capacity: usize,
+    /// Maximum number of ancestor blocks requested (and served) in a single
+    /// `SyncRangeRequest`/`SyncRangeReply`, regardless of how far back
+    /// `up_to_round` asks to go. Used by the `Synchronizer` only; `Helper::spawn`
+    /// takes its serving-side limit as a separate `max_range_len` argument.
+    pub max_range_len: RoundNumber,
+}
+
+impl Default for SyncConfig {
+    fn default() -> Self {
+        Self {
+            max_pending_parents: 10_000,
+            max_pending_payloads: 10_000,
+            inner_channel_capacity: 1_000,
+            max_range_len: 3,
+        }
+    }
+}
+
+/// Point-in-time snapshot of the synchronizer's internal buffering pressure.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SyncMetrics {
+    pub pending_parents: usize,
+    pub pending_payloads: usize,
+    pub in_flight_waiters: usize,
+    pub sync_requests_sent: usize,
+}
+
+#[derive(Default)]
+struct SyncCounters {
+    in_flight_waiters: AtomicUsize,
+    sync_requests_sent: AtomicUsize,
+}
+
+/// Capacity of the broadcast channel backing `Synchronizer::subscribe`. Slow
+/// subscribers simply miss the oldest events rather than stalling the sync task.
+const EVENT_CHANNEL_CAPACITY: usize = 1_000;
+
+/// A progress event emitted by the spawned synchronizer task as a pending item
+/// transitions state. `digest` identifies the block whose sync is progressing
+/// (the block waiting on the missing parent, or the block whose payload is
+/// pending). This matches the keys of `pending().pending_parents`; pending
+/// payloads are listed there by round instead.
+#[derive(Clone, Debug)]
+pub enum SyncEvent {
+    /// Sync for this digest has started: its missing parent has been requested
+    /// from the network, or its payload is now being waited on.
+    Requested(Digest),
+    /// What this digest's block was waiting on is now in the store, and the
+    /// block has been handed back for processing.
+    Delivered(Digest),
+    /// The sync for this digest was abandoned, with a human-readable reason.
+    Failed(Digest, String),
+}
+
+/// Snapshot of everything the `Synchronizer` is still waiting on.
+#[derive(Clone, Debug, Default)]
+pub struct SyncQuery {
+    /// Digests of blocks waiting on a missing parent, with the round they are at.
+    pub pending_parents: Vec<(Digest, RoundNumber)>,
+    /// Rounds whose payload has not yet been delivered.
+    pub pending_payloads: Vec<RoundNumber>,
+}
+
 pub struct Synchronizer {
     name: PublicKey,
+    committee: Committee,
     store: Store,
     inner_channel: Sender<SyncMessage>,
     network_channel: Sender<NetMessage>,
     pending_payloads: HashMap<RoundNumber, Sender<()>>,
+    config: SyncConfig,
+    counters: Arc<SyncCounters>,
+    pending_parent_rounds: Arc<Mutex<HashMap<Digest, RoundNumber>>>,
+    event_channel: broadcast::Sender<SyncEvent>,
+    /// Outstanding `SyncRangeRequest`s we issued ourselves, keyed by the digest we
+    /// asked to start from, so an incoming `SyncRangeReply` can be checked against
+    /// something we actually requested before it is trusted.
+    pending_ranges: HashMap<Digest, RoundNumber>,
 }
 
 impl Synchronizer {
     pub async fn new(
         name: PublicKey,
+        committee: Committee,
+        others: Vec<PublicKey>,
         store: Store,
         network_channel: Sender<NetMessage>,
         core_channel: Sender<CoreMessage>,
         mut timer_manager: TimerManager,
         sync_retry_delay: u64,
+        config: SyncConfig,
     ) -> Self {
-        let (tx_inner, mut rx_inner): (_, Receiver<SyncMessage>) = channel(1000);
+        let (tx_inner, mut rx_inner): (_, Receiver<SyncMessage>) =
+            channel(config.inner_channel_capacity);
         let (tx_timer, mut rx_timer) = channel(100);
         timer_manager
             .schedule(sync_retry_delay, "sync".to_string(), tx_timer.clone())
@@ -48,47 +202,104 @@ impl Synchronizer {
 
         let store_copy = store.clone();
         let network_channel_copy = network_channel.clone();
+        let counters = Arc::new(SyncCounters::default());
+        let counters_copy = counters.clone();
+        let pending_parent_rounds = Arc::new(Mutex::new(HashMap::new()));
+        let pending_parent_rounds_copy = pending_parent_rounds.clone();
+        let (event_channel, _) = broadcast::channel(EVENT_CHANNEL_CAPACITY);
+        let event_channel_copy = event_channel.clone();
         tokio::spawn(async move {
             let mut waiting = FuturesUnordered::new();
-            let mut pending_parents = HashSet::new();
+            // Only guards against spawning a second waiter for a block we are
+            // already waiting on; the pending-parent cap itself is enforced
+            // synchronously by `get_previous_block`, not here.
+            let mut waited_on = HashSet::new();
+            let mut retry_states: HashMap<Digest, RetryState> = HashMap::new();
             loop {
                 select! {
                     message = rx_inner.recv().fuse() => {
                         match message {
                             Some(SyncMessage::SyncParent(wait_on, block)) => {
-                                if pending_parents.insert(block.digest()) {
-                                    let fut = Self::waiter(store_copy.clone(), wait_on, block, None);
+                                if waited_on.insert(block.digest()) {
+                                    retry_states.insert(
+                                        block.digest(),
+                                        RetryState::new(wait_on.clone(), block.author, sync_retry_delay),
+                                    );
+                                    counters_copy.in_flight_waiters.fetch_add(1, Ordering::Relaxed);
+                                    let _ = event_channel_copy.send(SyncEvent::Requested(block.digest()));
+                                    let fut = Self::waiter(store_copy.clone(), wait_on, block, None, event_channel_copy.clone());
                                     waiting.push(fut);
                                 }
                             },
                             Some(SyncMessage::SyncPayload(wait_on, block, cancellation_handler)) => {
-                                let fut = Self::waiter(store_copy.clone(), wait_on, block, Some(cancellation_handler));
+                                counters_copy.in_flight_waiters.fetch_add(1, Ordering::Relaxed);
+                                let fut = Self::waiter(store_copy.clone(), wait_on, block, Some(cancellation_handler), event_channel_copy.clone());
                                 waiting.push(fut);
                             },
                             _ => ()
                         }
                     },
-                    result = waiting.select_next_some() => {
-                        match result {
-                            Ok(Some(block)) => {
-                                let _ = pending_parents.remove(&block.digest());
-                                let message = CoreMessage::LoopBack(block);
-                                if let Err(e) = core_channel.send(message).await {
-                                    panic!("Failed to send message through core channel: {}", e);
-                                }
-                            },
-                            Ok(None) => (),
-                            Err(e) => error!("{}", e)
+                    (digest, outcome) = waiting.select_next_some() => {
+                        counters_copy.in_flight_waiters.fetch_sub(1, Ordering::Relaxed);
+                        // Whether the wait delivered, was cancelled, or hit a store
+                        // error, it is done: release the slot either way, so a wait
+                        // that errors out can't leak its pending-parent reservation
+                        // forever.
+                        if waited_on.remove(&digest) {
+                            pending_parent_rounds_copy.lock().unwrap().remove(&digest);
+                        }
+                        let _ = retry_states.remove(&digest);
+                        if let Some(block) = outcome {
+                            let message = CoreMessage::LoopBack(block);
+                            if let Err(e) = core_channel.send(message).await {
+                                panic!("Failed to send message through core channel: {}", e);
+                            }
                         }
                     },
                     notification = rx_timer.recv().fuse() => {
                         if notification.is_some() {
-                            // This ensure liveness in case Sync Requests are lost.
-                            for digest in &pending_parents {
-                                let sync_request = NetMessage::SyncRequest(digest.clone(), name);
-                                if let Err(e) = network_channel_copy.send(sync_request).await {
-                                    panic!("Failed to send Sync Request to network: {}", e);
+                            // This ensures liveness in case earlier Sync Requests were lost.
+                            // Each retry targets the block's author first, then widens to
+                            // other peers round-robin, and only broadcasts to everyone once
+                            // MAX_TARGETED_ATTEMPTS targeted attempts have failed. Once a
+                            // digest has been retried MAX_RETRY_ATTEMPTS times with no
+                            // delivery, give up on it entirely rather than retrying forever:
+                            // that frees its pending-parent slot for something resolvable
+                            // and tells subscribers the sync failed instead of going silent.
+                            let now = Instant::now();
+                            let mut gave_up = Vec::new();
+                            for (digest, state) in retry_states.iter_mut() {
+                                if now < state.next_due {
+                                    continue;
+                                }
+                                if state.attempt >= MAX_RETRY_ATTEMPTS {
+                                    gave_up.push(digest.clone());
+                                    continue;
                                 }
+                                if state.attempt < MAX_TARGETED_ATTEMPTS {
+                                    let target = state.next_target(&others);
+                                    let sync_request =
+                                        NetMessage::SyncRequestTo(state.wait_on.clone(), name, target);
+                                    if let Err(e) = network_channel_copy.send(sync_request).await {
+                                        panic!("Failed to send Sync Request to network: {}", e);
+                                    }
+                                } else {
+                                    let sync_request = NetMessage::SyncRequest(state.wait_on.clone(), name);
+                                    if let Err(e) = network_channel_copy.send(sync_request).await {
+                                        panic!("Failed to send Sync Request to network: {}", e);
+                                    }
+                                }
+                                counters_copy.sync_requests_sent.fetch_add(1, Ordering::Relaxed);
+                                state.backoff(sync_retry_delay);
+                            }
+                            for digest in gave_up {
+                                retry_states.remove(&digest);
+                                waited_on.remove(&digest);
+                                pending_parent_rounds_copy.lock().unwrap().remove(&digest);
+                                let _ = event_channel_copy.send(SyncEvent::Failed(
+                                    digest,
+                                    format!("gave up after {} retry attempts", MAX_RETRY_ATTEMPTS),
+                                ));
                             }
                             timer_manager
                                 .schedule(sync_retry_delay, "sync".to_string(), tx_timer.clone())
@@ -100,32 +311,97 @@ impl Synchronizer {
         });
         Self {
             name,
+            committee,
             store,
             inner_channel: tx_inner,
             network_channel,
             pending_payloads: HashMap::new(),
+            config,
+            counters,
+            pending_parent_rounds,
+            event_channel,
+            pending_ranges: HashMap::new(),
         }
     }
 
+    /// Snapshot the synchronizer's current buffering pressure for observability.
+    pub fn metrics(&self) -> SyncMetrics {
+        SyncMetrics {
+            pending_parents: self.pending_parent_rounds.lock().unwrap().len(),
+            pending_payloads: self.pending_payloads.len(),
+            in_flight_waiters: self.counters.in_flight_waiters.load(Ordering::Relaxed),
+            sync_requests_sent: self.counters.sync_requests_sent.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Subscribe to `SyncEvent`s for every pending parent and payload, so callers
+    /// (telemetry, or core's own progress reporting) can watch catch-up progress
+    /// instead of polling the store blindly.
+    pub fn subscribe(&self) -> broadcast::Receiver<SyncEvent> {
+        self.event_channel.subscribe()
+    }
+
+    /// Snapshot of the digests (and, for parents, their round) that are still
+    /// outstanding.
+    pub fn pending(&self) -> SyncQuery {
+        let pending_parents = self
+            .pending_parent_rounds
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(digest, round)| (digest.clone(), *round))
+            .collect();
+        let pending_payloads = self.pending_payloads.keys().copied().collect();
+        SyncQuery {
+            pending_parents,
+            pending_payloads,
+        }
+    }
+
+    /// Wait for `wait_on` to show up in the store, or give up early on
+    /// cancellation or a store error. Always returns `deliver`'s digest
+    /// alongside the outcome, so the caller can release whatever bookkeeping
+    /// it is keeping for that digest no matter which way the wait ends -
+    /// including the store-error case, which used to be dropped on the floor
+    /// without ever freeing the caller's pending-parent slot.
     async fn waiter(
         mut store: Store,
         wait_on: Vec<u8>,
         deliver: Block,
         cancellation: Option<Receiver<()>>,
-    ) -> ConsensusResult<Option<Block>> {
-        if let Some(mut cancellation) = cancellation {
+        event_channel: broadcast::Sender<SyncEvent>,
+    ) -> (Digest, Option<Block>) {
+        let digest = deliver.digest();
+        let delivered = if let Some(mut cancellation) = cancellation {
             select! {
                 result = store.notify_read(wait_on).fuse() => {
-                    let _ = result?;
-                    Ok(Some(deliver))
+                    match result {
+                        Ok(_) => true,
+                        Err(e) => {
+                            let _ = event_channel.send(SyncEvent::Failed(digest, e.to_string()));
+                            false
+                        }
+                    }
                 },
                 _ = cancellation.recv().fuse() => {
-                    Ok(None)
+                    let _ = event_channel.send(SyncEvent::Failed(digest, "sync request cancelled".to_string()));
+                    false
                 }
             }
         } else {
-            let _ = store.notify_read(wait_on).await?;
-            Ok(Some(deliver))
+            match store.notify_read(wait_on).await {
+                Ok(_) => true,
+                Err(e) => {
+                    let _ = event_channel.send(SyncEvent::Failed(digest, e.to_string()));
+                    false
+                }
+            }
+        };
+        if delivered {
+            let _ = event_channel.send(SyncEvent::Delivered(digest));
+            (digest, Some(deliver))
+        } else {
+            (digest, None)
         }
     }
 
@@ -137,10 +413,32 @@ impl Synchronizer {
         match self.store.read(parent.to_vec()).await? {
             Some(bytes) => Ok(Some(bincode::deserialize(&bytes)?)),
             None => {
+                let digest = block.digest();
+                {
+                    // Reserve the cap slot synchronously, here, rather than relying on
+                    // the spawned task to bump a counter once it gets around to
+                    // processing the `SyncParent` message: several `get_previous_block`
+                    // calls can race ahead of that task, and a count it updates only
+                    // asynchronously can't reject them exactly at the cap.
+                    let mut pending = self.pending_parent_rounds.lock().unwrap();
+                    if !pending.contains_key(&digest) {
+                        if pending.len() >= self.config.max_pending_parents {
+                            return Err(ConsensusError::TooManyPendingParents(
+                                self.config.max_pending_parents,
+                            ));
+                        }
+                        pending.insert(digest, block.round);
+                    }
+                }
                 debug!("Requesting sync for block {:?}", parent);
-                let message = NetMessage::SyncRequest(parent.clone(), self.name);
+                // Ask for a whole run of ancestors in one shot rather than one parent
+                // at a time, so a replica that is several blocks behind heals in a
+                // bounded number of round trips instead of a chain of them.
+                let up_to_round = block.round.saturating_sub(self.config.max_range_len);
+                self.pending_ranges.insert(parent.clone(), up_to_round);
+                let message = NetMessage::SyncRangeRequest(parent.clone(), up_to_round, self.name);
                 if let Err(e) = self.network_channel.send(message).await {
-                    panic!("Failed to send Sync Request to network: {}", e);
+                    panic!("Failed to send Sync Range Request to network: {}", e);
                 }
                 let message = SyncMessage::SyncParent(parent.to_vec(), block.clone());
                 if let Err(e) = self.inner_channel.send(message).await {
@@ -170,17 +468,125 @@ impl Synchronizer {
         Ok(Some((b0, b1, b2)))
     }
 
-    pub async fn register_payload(&mut self, block: &Block) {
+    /// Handle a batch of ancestor blocks received in response to a `SyncRangeRequest`
+    /// we issued for `from_digest`. The reply is untrusted input: we only accept it
+    /// if it answers a request we actually made, stays within `config.max_range_len`
+    /// blocks, forms an unbroken chain back from `from_digest` down to the
+    /// `up_to_round` floor we asked for using nothing but the blocks it carried,
+    /// and every block in that chain passes the same signature/QC verification
+    /// normal block delivery applies. Anything else is dropped rather than
+    /// written to the store.
+    pub async fn handle_sync_range_reply(
+        &mut self,
+        from_digest: Digest,
+        blocks: Vec<Block>,
+    ) -> ConsensusResult<()> {
+        if blocks.len() > self.config.max_range_len as usize {
+            debug!(
+                "Dropping sync range reply for {:?}: {} blocks exceeds the cap of {}",
+                from_digest,
+                blocks.len(),
+                self.config.max_range_len
+            );
+            return Ok(());
+        }
+
+        // Peek rather than remove: a malformed or unrelated reply must not burn the
+        // reservation, or a later reply from an honest peer answering the same
+        // request would itself be dropped as "never asked for". We only clear the
+        // reservation once a reply actually passes validation, below.
+        let up_to_round = match self.pending_ranges.get(&from_digest).copied() {
+            Some(up_to_round) => up_to_round,
+            None => {
+                debug!("Dropping sync range reply for a request we never made: {:?}", from_digest);
+                return Ok(());
+            }
+        };
+
+        let mut by_digest: HashMap<Digest, Block> =
+            blocks.into_iter().map(|block| (block.digest(), block)).collect();
+
+        // Walk the chain back from `from_digest`, consuming each block as we use it.
+        // A reply can only pass this if every block in it is actually part of the
+        // requested chain; anything left over afterwards is unrelated and rejected.
+        let mut chain = Vec::new();
+        let mut next = Some(from_digest.clone());
+        while let Some(digest) = next {
+            let block = match by_digest.remove(&digest) {
+                Some(block) => block,
+                None => {
+                    debug!("Dropping sync range reply: does not chain back to {:?}", from_digest);
+                    return Ok(());
+                }
+            };
+            next = if block.round <= up_to_round {
+                None
+            } else {
+                Some(block.previous().clone())
+            };
+            chain.push(block);
+        }
+
+        if !by_digest.is_empty() {
+            debug!(
+                "Dropping sync range reply for {:?}: contained {} unrelated block(s)",
+                from_digest,
+                by_digest.len()
+            );
+            return Ok(());
+        }
+
+        // Digest-chaining alone only proves internal self-consistency: a colluding
+        // peer can fabricate a whole chain that is perfectly linked but signed by
+        // nobody. Run every block through the same signature/QC check that normal
+        // block delivery applies before it ever reaches the store.
+        for block in &chain {
+            if let Err(e) = block.verify(&self.committee) {
+                debug!(
+                    "Dropping sync range reply for {:?}: block {:?} failed verification: {}",
+                    from_digest,
+                    block.digest(),
+                    e
+                );
+                return Ok(());
+            }
+        }
+
+        self.pending_ranges.remove(&from_digest);
+        chain.sort_by_key(|block| block.round);
+        for block in chain {
+            let key = block.digest().to_vec();
+            let value = bincode::serialize(&block)?;
+            self.store.write(key, value).await;
+        }
+        Ok(())
+    }
+
+    /// Register interest in `block`'s payload and return a subscription to the
+    /// `SyncEvent`s (`Requested`/`Delivered`/`Failed`) that track its progress.
+    pub async fn register_payload(
+        &mut self,
+        block: &Block,
+    ) -> ConsensusResult<broadcast::Receiver<SyncEvent>> {
+        // Subscribe before emitting `Requested`, so the caller's receiver sees it.
+        let events = self.event_channel.subscribe();
         if !self.pending_payloads.contains_key(&block.round) {
+            if self.pending_payloads.len() >= self.config.max_pending_payloads {
+                return Err(ConsensusError::TooManyPendingPayloads(
+                    self.config.max_pending_payloads,
+                ));
+            }
             let (tx_cancellation, rx_cancellation) = channel(1);
             let round = block.round;
             self.pending_payloads.insert(round, tx_cancellation);
+            let _ = self.event_channel.send(SyncEvent::Requested(block.digest()));
             let message =
                 SyncMessage::SyncPayload(block.payload.clone(), block.clone(), rx_cancellation);
             if let Err(e) = self.inner_channel.send(message).await {
                 panic!("Failed to send request to synchronizer: {}", e);
             }
         }
+        Ok(events)
     }
 
     pub async fn cleanup(&mut self, round: RoundNumber) {
@@ -0,0 +1,38 @@
+//! `handle_request`/`handle_range_request` themselves aren't exercised here:
+//! doing so needs a real `Store` (backed by `crate::store`, not present in
+//! this checkout) and a `Block` fixture wired through `crate::messages`, so
+//! for now this module only covers the budget-charging logic those two
+//! handlers both call into.
+
+use super::*;
+use crate::crypto::PublicKey;
+use std::collections::HashMap;
+
+fn public_key(byte: u8) -> PublicKey {
+    PublicKey([byte; 32])
+}
+
+#[test]
+fn charge_budget_allows_up_to_the_limit() {
+    let mut budgets = HashMap::new();
+    let peer = public_key(1);
+    for _ in 0..MAX_REQUESTS_PER_PEER {
+        assert!(charge_budget(&mut budgets, peer));
+    }
+    assert!(!charge_budget(&mut budgets, peer));
+}
+
+#[test]
+fn charge_budget_tracks_peers_independently() {
+    let mut budgets = HashMap::new();
+    let alice = public_key(1);
+    let bob = public_key(2);
+
+    for _ in 0..MAX_REQUESTS_PER_PEER {
+        assert!(charge_budget(&mut budgets, alice));
+    }
+    assert!(!charge_budget(&mut budgets, alice));
+
+    // Bob's budget is unaffected by Alice exhausting hers.
+    assert!(charge_budget(&mut budgets, bob));
+}
@@ -0,0 +1,59 @@
+//! `handle_sync_range_reply`'s accept/reject paths and the
+//! `TooManyPendingParents`/`TooManyPendingPayloads` cap rejections aren't
+//! exercised here: both need a real `Store`, signed `Block`s and a
+//! `Committee` to construct (`crate::store`, `crate::messages` and
+//! `crate::config` aren't present in this checkout), so for now this module
+//! only covers the pure, dependency-free pieces - retry bookkeeping and
+//! `SyncConfig`'s defaults.
+
+use super::*;
+use crate::crypto::PublicKey;
+
+fn public_key(byte: u8) -> PublicKey {
+    PublicKey([byte; 32])
+}
+
+#[test]
+fn retry_state_targets_author_first_then_round_robins() {
+    let author = public_key(1);
+    let others = vec![public_key(2), public_key(3)];
+    let mut state = RetryState::new(b"digest".to_vec(), author, 100);
+
+    assert_eq!(state.next_target(&others), author);
+    assert_eq!(state.next_target(&others), public_key(2));
+    assert_eq!(state.next_target(&others), public_key(3));
+}
+
+#[test]
+fn retry_state_falls_back_to_author_once_everyone_is_queried() {
+    let author = public_key(1);
+    let others = vec![public_key(2)];
+    let mut state = RetryState::new(b"digest".to_vec(), author, 100);
+
+    state.next_target(&others); // author
+    state.next_target(&others); // public_key(2)
+    assert_eq!(state.next_target(&others), author);
+}
+
+#[test]
+fn retry_state_backoff_doubles_and_caps_at_the_ceiling() {
+    let mut state = RetryState::new(b"digest".to_vec(), public_key(1), 10);
+
+    state.backoff(10);
+    assert_eq!(state.delay, 20);
+    state.backoff(10);
+    assert_eq!(state.delay, 40);
+
+    for _ in 0..10 {
+        state.backoff(10);
+    }
+    assert_eq!(state.delay, 10 * RETRY_DELAY_CEILING_MULTIPLIER);
+}
+
+#[test]
+fn sync_config_default_is_bounded() {
+    let config = SyncConfig::default();
+    assert!(config.max_pending_parents > 0);
+    assert!(config.max_pending_payloads > 0);
+    assert!(config.inner_channel_capacity > 0);
+}